@@ -0,0 +1,524 @@
+use crate::{FileMode, GitVfs, GitVfsError, GitVfsResult, ObjectFormat, ObjectKind};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
+use std::io::{Read, Write};
+
+impl GitVfs {
+    /// Serializes the objects reachable from `refs` (excluding anything
+    /// reachable only through `exclude`, whose commits become `prerequisite`
+    /// lines) as a git bundle: a `# v2 git bundle` signature for SHA-1
+    /// stores (or `# v3 git bundle` plus an `@object-format=sha256`
+    /// capability line for SHA-256 stores), prerequisite and
+    /// ref-advertisement lines, a blank line, then a packed-objects section.
+    ///
+    /// The packed section is a real (if minimal) non-delta packfile: a
+    /// `PACK` magic, version, and object count, followed by each object as
+    /// a type+size header (git's pack varint encoding) and its zlib-deflated
+    /// raw content, then a trailing content checksum (under the configured
+    /// [`ObjectFormat`]) over everything from `PACK` up to the checksum
+    /// itself. There's no delta compression — every object is stored in
+    /// full — but the bytes are otherwise pack-shaped.
+    pub fn export_bundle<W: Write>(
+        &self,
+        refs: &[&str],
+        exclude: &[&str],
+        writer: &mut W,
+    ) -> GitVfsResult<()> {
+        let io_err = |_| GitVfsError::InvalidOperation;
+
+        let mut excluded_commits = HashSet::new();
+        for name in exclude {
+            excluded_commits.insert(self.resolve_ref(name)?);
+        }
+
+        let mut reachable = HashSet::new();
+        let mut advertisements = Vec::new();
+        for name in refs {
+            let advertised = self.resolve_symbolic(name)?;
+            let commit_oid = self.peel_through_tags(&advertised, &mut reachable)?;
+            advertisements.push((advertised, (*name).to_string()));
+
+            for commit_oid in self.collect_commit_oids(&commit_oid, &excluded_commits)? {
+                reachable.insert(commit_oid.clone());
+                let commit = self.read_commit(&commit_oid)?;
+                self.collect_tree_closure(&commit.tree, &mut reachable)?;
+            }
+        }
+
+        match self.format {
+            ObjectFormat::Sha1 => writeln!(writer, "# v2 git bundle").map_err(io_err)?,
+            ObjectFormat::Sha256 => {
+                writeln!(writer, "# v3 git bundle").map_err(io_err)?;
+                writeln!(writer, "@object-format={}", ObjectFormat::Sha256.capability_name())
+                    .map_err(io_err)?;
+            }
+        }
+        for oid in &excluded_commits {
+            writeln!(writer, "-{oid}").map_err(io_err)?;
+        }
+        for (oid, name) in &advertisements {
+            writeln!(writer, "{oid} {name}").map_err(io_err)?;
+        }
+        writeln!(writer).map_err(io_err)?;
+
+        let mut pack = Vec::new();
+        pack.extend_from_slice(b"PACK");
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(reachable.len() as u32).to_be_bytes());
+        for oid in &reachable {
+            let data = self.get_object(oid)?;
+            let kind = self.kind_of(oid, &data)?;
+            write_object_header(&mut pack, kind, data.len());
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&data).map_err(io_err)?;
+            pack.extend_from_slice(&encoder.finish().map_err(io_err)?);
+        }
+        let checksum = raw_digest(self.format, &pack);
+
+        writer.write_all(&pack).map_err(io_err)?;
+        writer.write_all(&checksum).map_err(io_err)?;
+        Ok(())
+    }
+
+    /// Loads objects and refs from a bundle written by [`GitVfs::export_bundle`].
+    /// Every packed object's id is re-derived by hashing its decompressed
+    /// content (so a corrupted or hand-crafted entry can never be inserted
+    /// under an id it doesn't actually match), and the pack's trailing
+    /// checksum is verified before anything is applied to this store.
+    pub fn import_bundle<R: Read>(&mut self, reader: &mut R) -> GitVfsResult<()> {
+        let mut content = Vec::new();
+        reader
+            .read_to_end(&mut content)
+            .map_err(|_| GitVfsError::InvalidOperation)?;
+        let mut pos = 0;
+
+        let signature = read_line(&content, &mut pos)?;
+        if signature != "# v2 git bundle" && signature != "# v3 git bundle" {
+            return Err(GitVfsError::InvalidOperation);
+        }
+
+        let mut declared_format = None;
+        let mut prerequisites = Vec::new();
+        let mut advertisements = Vec::new();
+        loop {
+            let line = read_line(&content, &mut pos)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("@object-format=") {
+                declared_format =
+                    Some(ObjectFormat::from_capability_name(value).ok_or(GitVfsError::InvalidOperation)?);
+                continue;
+            }
+            if line.starts_with('@') {
+                // Unrecognized capability line; ignore.
+                continue;
+            }
+            if let Some(oid) = line.strip_prefix('-') {
+                prerequisites.push(oid.to_string());
+                continue;
+            }
+            let (oid, refname) = line.split_once(' ').ok_or(GitVfsError::InvalidOperation)?;
+            advertisements.push((oid.to_string(), refname.to_string()));
+        }
+
+        if let Some(declared) = declared_format {
+            if declared != self.format {
+                return Err(GitVfsError::InvalidOperation);
+            }
+        } else if self.format != ObjectFormat::Sha1 {
+            // v2 bundles (no object-format capability) are implicitly SHA-1.
+            return Err(GitVfsError::InvalidOperation);
+        }
+
+        let pack_start = pos;
+        if content.get(pos..pos + 4) != Some(&b"PACK"[..]) {
+            return Err(GitVfsError::InvalidOperation);
+        }
+        pos += 4;
+        let version = u32::from_be_bytes(
+            content
+                .get(pos..pos + 4)
+                .ok_or(GitVfsError::InvalidOperation)?
+                .try_into()
+                .map_err(|_| GitVfsError::InvalidOperation)?,
+        );
+        if version != 2 {
+            return Err(GitVfsError::InvalidOperation);
+        }
+        pos += 4;
+        let count = u32::from_be_bytes(
+            content
+                .get(pos..pos + 4)
+                .ok_or(GitVfsError::InvalidOperation)?
+                .try_into()
+                .map_err(|_| GitVfsError::InvalidOperation)?,
+        );
+        pos += 4;
+
+        let mut unpacked = Vec::new();
+        for _ in 0..count {
+            let (kind, declared_len) = read_object_header(&content, &mut pos)?;
+
+            let mut decoder = ZlibDecoder::new(
+                content
+                    .get(pos..)
+                    .ok_or(GitVfsError::InvalidOperation)?,
+            );
+            let mut data = Vec::new();
+            decoder
+                .read_to_end(&mut data)
+                .map_err(|_| GitVfsError::InvalidOperation)?;
+            pos += decoder.total_in() as usize;
+            if data.len() != declared_len {
+                return Err(GitVfsError::InvalidOperation);
+            }
+
+            let oid = self.hash_object(kind, &data);
+            unpacked.push((oid, kind, data));
+        }
+
+        let pack_end = pos;
+        let checksum_len = self.format.byte_width();
+        let expected_checksum = content
+            .get(pos..pos + checksum_len)
+            .ok_or(GitVfsError::InvalidOperation)?;
+        if raw_digest(self.format, &content[pack_start..pack_end]) != expected_checksum {
+            return Err(GitVfsError::InvalidOperation);
+        }
+        pos += checksum_len;
+        if pos != content.len() {
+            return Err(GitVfsError::InvalidOperation);
+        }
+
+        for (oid, kind, data) in unpacked {
+            self.objects.entry(oid).or_insert((Some(kind), data));
+        }
+
+        for prerequisite in &prerequisites {
+            if !self.objects.contains_key(prerequisite) {
+                return Err(GitVfsError::InvalidOperation);
+            }
+        }
+
+        for (oid, refname) in advertisements {
+            self.refs.insert(refname, oid);
+        }
+        Ok(())
+    }
+
+    /// Follows `object <oid>` links out of annotated tag objects, recording
+    /// each tag object visited along the way, and returns the final (tag
+    /// chain tip's) commit oid.
+    fn peel_through_tags(&self, oid: &str, reachable: &mut HashSet<String>) -> GitVfsResult<String> {
+        let mut current = oid.to_string();
+        loop {
+            reachable.insert(current.clone());
+            let data = self.get_object(&current)?;
+            let Some(rest) = data.strip_prefix(b"object ") else {
+                return Ok(current);
+            };
+            let newline = rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or(GitVfsError::InvalidOperation)?;
+            current = std::str::from_utf8(&rest[..newline])
+                .map_err(|_| GitVfsError::InvalidOperation)?
+                .to_string();
+        }
+    }
+
+    /// Breadth-first collects the oids of `start` and all its ancestors,
+    /// stopping at (and excluding) any commit in `excluded`.
+    fn collect_commit_oids(
+        &self,
+        start: &str,
+        excluded: &HashSet<String>,
+    ) -> GitVfsResult<Vec<String>> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start.to_string());
+        let mut result = Vec::new();
+        while let Some(oid) = queue.pop_front() {
+            if excluded.contains(&oid) || !visited.insert(oid.clone()) {
+                continue;
+            }
+            let commit = self.read_commit(&oid)?;
+            result.push(oid);
+            for parent in &commit.parents {
+                queue.push_back(parent.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    /// Recursively adds `tree_hash` and every tree/blob it reaches to
+    /// `reachable`.
+    fn collect_tree_closure(
+        &self,
+        tree_hash: &str,
+        reachable: &mut HashSet<String>,
+    ) -> GitVfsResult<()> {
+        if !reachable.insert(tree_hash.to_string()) {
+            return Ok(());
+        }
+        for entry in self.read_tree(tree_hash)? {
+            if entry.mode == FileMode::Directory {
+                self.collect_tree_closure(&entry.hash, reachable)?;
+            } else {
+                reachable.insert(entry.hash);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maps an [`ObjectKind`] to the type code git's pack object header uses.
+fn type_code(kind: ObjectKind) -> u8 {
+    match kind {
+        ObjectKind::Commit => 1,
+        ObjectKind::Tree => 2,
+        ObjectKind::Blob => 3,
+        ObjectKind::Tag => 4,
+    }
+}
+
+fn kind_from_code(code: u8) -> GitVfsResult<ObjectKind> {
+    match code {
+        1 => Ok(ObjectKind::Commit),
+        2 => Ok(ObjectKind::Tree),
+        3 => Ok(ObjectKind::Blob),
+        4 => Ok(ObjectKind::Tag),
+        _ => Err(GitVfsError::InvalidOperation),
+    }
+}
+
+/// Appends a pack object header (`kind`/`size`) in git's varint encoding:
+/// the low 4 bits of `size` share the first byte with the type code, and
+/// each following byte (while the high bit is set) contributes 7 more bits.
+fn write_object_header(buf: &mut Vec<u8>, kind: ObjectKind, size: usize) {
+    let mut c = (type_code(kind) << 4) | ((size & 0x0f) as u8);
+    let mut size = size >> 4;
+    loop {
+        if size == 0 {
+            buf.push(c);
+            break;
+        }
+        buf.push(c | 0x80);
+        c = (size & 0x7f) as u8;
+        size >>= 7;
+    }
+}
+
+fn read_object_header(data: &[u8], pos: &mut usize) -> GitVfsResult<(ObjectKind, usize)> {
+    let first = *data.get(*pos).ok_or(GitVfsError::InvalidOperation)?;
+    *pos += 1;
+    let kind = kind_from_code((first >> 4) & 0x7)?;
+    let mut size = (first & 0x0f) as usize;
+    let mut shift = 4;
+    let mut byte = first;
+    while byte & 0x80 != 0 {
+        byte = *data.get(*pos).ok_or(GitVfsError::InvalidOperation)?;
+        *pos += 1;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+    }
+    Ok((kind, size))
+}
+
+/// The raw (non-hex) digest of `data` under `format`, used for the pack
+/// trailer checksum.
+fn raw_digest(format: ObjectFormat, data: &[u8]) -> Vec<u8> {
+    match format {
+        ObjectFormat::Sha1 => {
+            let mut hasher = Sha1::new();
+            hasher.update(data);
+            hasher.finalize().to_vec()
+        }
+        ObjectFormat::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(data);
+            hasher.finalize().to_vec()
+        }
+    }
+}
+
+fn read_line(content: &[u8], pos: &mut usize) -> GitVfsResult<String> {
+    let newline = content[*pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or(GitVfsError::InvalidOperation)?
+        + *pos;
+    let line = std::str::from_utf8(&content[*pos..newline])
+        .map_err(|_| GitVfsError::InvalidOperation)?
+        .to_string();
+    *pos = newline + 1;
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Signature;
+
+    fn sig() -> Signature {
+        Signature {
+            name: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            timestamp: 1,
+            tz: "+0000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_export_then_import_round_trips_objects_and_refs() {
+        let mut vfs = GitVfs::new();
+        let blob = vfs.create_blob(b"hello").expect("create blob");
+        let tree = vfs
+            .create_tree(&[crate::TreeEntry {
+                mode: FileMode::Regular,
+                name: "file.txt".to_string(),
+                hash: blob,
+            }])
+            .expect("create tree");
+        let commit = vfs
+            .create_commit(&tree, &[], sig(), sig(), "root")
+            .expect("create commit");
+        vfs.create_ref("refs/heads/main", &commit)
+            .expect("create ref");
+
+        let mut bundle = Vec::new();
+        vfs.export_bundle(&["refs/heads/main"], &[], &mut bundle)
+            .expect("export bundle");
+
+        let mut imported = GitVfs::new();
+        imported
+            .import_bundle(&mut bundle.as_slice())
+            .expect("import bundle");
+
+        assert_eq!(
+            imported.get_ref("refs/heads/main").unwrap(),
+            commit
+        );
+        let imported_commit = imported.read_commit(&commit).expect("read commit");
+        assert_eq!(imported_commit.message, "root");
+        let imported_entries = imported.read_tree(&imported_commit.tree).expect("read tree");
+        assert_eq!(imported_entries[0].name, "file.txt");
+        assert_eq!(
+            imported.get_object(&imported_entries[0].hash).unwrap(),
+            b"hello"
+        );
+    }
+
+    #[test]
+    fn test_export_excludes_prerequisite_commit_ancestry() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let base = vfs
+            .create_commit(&tree, &[], sig(), sig(), "base")
+            .expect("create base commit");
+        let head = vfs
+            .create_commit(&tree, std::slice::from_ref(&base), sig(), sig(), "head")
+            .expect("create head commit");
+        vfs.create_ref("refs/heads/base", &base)
+            .expect("create ref");
+        vfs.create_ref("refs/heads/main", &head)
+            .expect("create ref");
+
+        let mut bundle = Vec::new();
+        vfs.export_bundle(&["refs/heads/main"], &["refs/heads/base"], &mut bundle)
+            .expect("export bundle");
+
+        // Only `head` and its (shared, empty) tree are packed; `base` is not.
+        let pack_start = bundle
+            .windows(4)
+            .position(|w| w == b"PACK")
+            .expect("pack magic present");
+        let count = u32::from_be_bytes(bundle[pack_start + 8..pack_start + 12].try_into().unwrap());
+        assert_eq!(count, 2);
+
+        // A receiver can only apply this bundle if it already has the
+        // excluded prerequisite commit (e.g. from an earlier, fuller clone).
+        let mut imported = GitVfs::new();
+        let imported_tree = imported.create_tree(&[]).expect("create tree");
+        let imported_base = imported
+            .create_commit(&imported_tree, &[], sig(), sig(), "base")
+            .expect("recreate base commit");
+        assert_eq!(imported_base, base);
+
+        imported
+            .import_bundle(&mut bundle.as_slice())
+            .expect("import bundle");
+
+        assert!(imported.read_commit(&head).is_ok());
+    }
+
+    #[test]
+    fn test_import_rejects_missing_prerequisite() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let commit = vfs
+            .create_commit(&tree, &[], sig(), sig(), "root")
+            .expect("create commit");
+        vfs.create_ref("refs/heads/main", &commit)
+            .expect("create ref");
+
+        let mut bundle = Vec::new();
+        vfs.export_bundle(&["refs/heads/main"], &[], &mut bundle)
+            .expect("export bundle");
+
+        // Tamper in a prerequisite line for an oid that was never packed.
+        let mut tampered = b"# v2 git bundle\n-deadbeef\n".to_vec();
+        let after_signature = bundle.splitn(2, |&b| b == b'\n').nth(1).unwrap();
+        tampered.extend_from_slice(after_signature);
+
+        let mut imported = GitVfs::new();
+        assert_eq!(
+            imported.import_bundle(&mut tampered.as_slice()),
+            Err(GitVfsError::InvalidOperation)
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_tampered_pack_bytes() {
+        let mut vfs = GitVfs::new();
+        let blob = vfs.create_blob(b"hello").expect("create blob");
+        let tree = vfs
+            .create_tree(&[crate::TreeEntry {
+                mode: FileMode::Regular,
+                name: "file.txt".to_string(),
+                hash: blob,
+            }])
+            .expect("create tree");
+        let commit = vfs
+            .create_commit(&tree, &[], sig(), sig(), "root")
+            .expect("create commit");
+        vfs.create_ref("refs/heads/main", &commit)
+            .expect("create ref");
+
+        let mut bundle = Vec::new();
+        vfs.export_bundle(&["refs/heads/main"], &[], &mut bundle)
+            .expect("export bundle");
+
+        // Flip a byte inside the packed-object section (just past the
+        // PACK/version/count header) so the pack no longer matches its
+        // trailing checksum.
+        let pack_start = bundle
+            .windows(4)
+            .position(|w| w == b"PACK")
+            .expect("pack magic present");
+        let tamper_at = pack_start + 12;
+        bundle[tamper_at] ^= 0xff;
+
+        let mut imported = GitVfs::new();
+        assert_eq!(
+            imported.import_bundle(&mut bundle.as_slice()),
+            Err(GitVfsError::InvalidOperation)
+        );
+    }
+}
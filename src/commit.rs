@@ -0,0 +1,279 @@
+use crate::{GitVfs, GitVfsError, GitVfsResult, ObjectKind};
+use std::collections::{HashSet, VecDeque};
+
+/// An author/committer line: `Name <email> <unix-ts> <tz>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    pub tz: String,
+}
+
+impl Signature {
+    fn to_line(&self) -> String {
+        format!(
+            "{} <{}> {} {}",
+            self.name, self.email, self.timestamp, self.tz
+        )
+    }
+
+    fn from_line(line: &str) -> GitVfsResult<Signature> {
+        let email_start = line.find('<').ok_or(GitVfsError::InvalidOperation)?;
+        let email_end = line.find('>').ok_or(GitVfsError::InvalidOperation)?;
+        if email_end < email_start {
+            return Err(GitVfsError::InvalidOperation);
+        }
+        let name = line[..email_start].trim().to_string();
+        let email = line[email_start + 1..email_end].to_string();
+        let mut rest = line[email_end + 1..].trim().splitn(2, ' ');
+        let timestamp: i64 = rest
+            .next()
+            .ok_or(GitVfsError::InvalidOperation)?
+            .parse()
+            .map_err(|_| GitVfsError::InvalidOperation)?;
+        let tz = rest
+            .next()
+            .ok_or(GitVfsError::InvalidOperation)?
+            .to_string();
+        Ok(Signature {
+            name,
+            email,
+            timestamp,
+            tz,
+        })
+    }
+}
+
+/// A parsed git commit object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub tree: String,
+    pub parents: Vec<String>,
+    pub author: Signature,
+    pub committer: Signature,
+    pub message: String,
+}
+
+impl GitVfs {
+    /// Serializes the canonical commit text (`tree`, zero or more `parent`
+    /// lines, `author`, `committer`, a blank line, then `message`) and
+    /// stores it, returning the commit's id.
+    pub fn create_commit(
+        &mut self,
+        tree: &str,
+        parents: &[String],
+        author: Signature,
+        committer: Signature,
+        message: &str,
+    ) -> GitVfsResult<String> {
+        let mut text = format!("tree {tree}\n");
+        for parent in parents {
+            text.push_str(&format!("parent {parent}\n"));
+        }
+        text.push_str(&format!("author {}\n", author.to_line()));
+        text.push_str(&format!("committer {}\n", committer.to_line()));
+        text.push('\n');
+        text.push_str(message);
+        self.store_object(ObjectKind::Commit, text.as_bytes())
+    }
+
+    /// Parses a commit object back into its typed fields.
+    pub fn read_commit(&self, hash: &str) -> GitVfsResult<Commit> {
+        let data = self.get_object(hash)?;
+        let text = String::from_utf8(data).map_err(|_| GitVfsError::InvalidOperation)?;
+        let (header, message) = text
+            .split_once("\n\n")
+            .ok_or(GitVfsError::InvalidOperation)?;
+
+        let mut tree = None;
+        let mut parents = Vec::new();
+        let mut author = None;
+        let mut committer = None;
+        for line in header.lines() {
+            if let Some(rest) = line.strip_prefix("tree ") {
+                tree = Some(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("parent ") {
+                parents.push(rest.to_string());
+            } else if let Some(rest) = line.strip_prefix("author ") {
+                author = Some(Signature::from_line(rest)?);
+            } else if let Some(rest) = line.strip_prefix("committer ") {
+                committer = Some(Signature::from_line(rest)?);
+            } else {
+                return Err(GitVfsError::InvalidOperation);
+            }
+        }
+
+        Ok(Commit {
+            tree: tree.ok_or(GitVfsError::InvalidOperation)?,
+            parents,
+            author: author.ok_or(GitVfsError::InvalidOperation)?,
+            committer: committer.ok_or(GitVfsError::InvalidOperation)?,
+            message: message.to_string(),
+        })
+    }
+
+    /// Walks the commit graph breadth-first starting at `start_ref`
+    /// (resolved via [`GitVfs::resolve_ref`], or treated as a raw commit id
+    /// if it doesn't resolve to anything), deduplicating commits already
+    /// visited.
+    pub fn log(&self, start_ref: &str) -> CommitLogIter<'_> {
+        let start = self
+            .resolve_ref(start_ref)
+            .unwrap_or_else(|_| start_ref.to_string());
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        CommitLogIter {
+            vfs: self,
+            queue,
+            visited: HashSet::new(),
+        }
+    }
+}
+
+/// Breadth-first iterator over a commit's ancestry, yielding each commit at
+/// most once. See [`GitVfs::log`].
+pub struct CommitLogIter<'a> {
+    vfs: &'a GitVfs,
+    queue: VecDeque<String>,
+    visited: HashSet<String>,
+}
+
+impl<'a> Iterator for CommitLogIter<'a> {
+    type Item = GitVfsResult<Commit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let oid = self.queue.pop_front()?;
+            if !self.visited.insert(oid.clone()) {
+                continue;
+            }
+            return Some(self.vfs.read_commit(&oid).inspect(|commit| {
+                for parent in &commit.parents {
+                    if !self.visited.contains(parent) {
+                        self.queue.push_back(parent.clone());
+                    }
+                }
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sig(name: &str) -> Signature {
+        Signature {
+            name: name.to_string(),
+            email: format!("{name}@example.com"),
+            timestamp: 1_700_000_000,
+            tz: "+0000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_create_and_read_commit_round_trips() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let hash = vfs
+            .create_commit(&tree, &[], sig("alice"), sig("alice"), "initial commit")
+            .expect("create commit");
+
+        let commit = vfs.read_commit(&hash).expect("read commit");
+        assert_eq!(commit.tree, tree);
+        assert!(commit.parents.is_empty());
+        assert_eq!(commit.author, sig("alice"));
+        assert_eq!(commit.message, "initial commit");
+    }
+
+    #[test]
+    fn test_log_walks_parent_chain_breadth_first() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let root = vfs
+            .create_commit(&tree, &[], sig("alice"), sig("alice"), "root")
+            .expect("create root commit");
+        let child = vfs
+            .create_commit(
+                &tree,
+                std::slice::from_ref(&root),
+                sig("alice"),
+                sig("alice"),
+                "child",
+            )
+            .expect("create child commit");
+
+        let messages: Vec<String> = vfs
+            .log(&child)
+            .map(|c| c.expect("commit should parse").message)
+            .collect();
+        assert_eq!(messages, vec!["child".to_string(), "root".to_string()]);
+    }
+
+    #[test]
+    fn test_log_deduplicates_merge_commits() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let root = vfs
+            .create_commit(&tree, &[], sig("alice"), sig("alice"), "root")
+            .expect("create root commit");
+        let left = vfs
+            .create_commit(&tree, std::slice::from_ref(&root), sig("alice"), sig("alice"), "left")
+            .expect("create left commit");
+        let right = vfs
+            .create_commit(&tree, std::slice::from_ref(&root), sig("alice"), sig("alice"), "right")
+            .expect("create right commit");
+        let merge = vfs
+            .create_commit(
+                &tree,
+                &[left.clone(), right.clone()],
+                sig("alice"),
+                sig("alice"),
+                "merge",
+            )
+            .expect("create merge commit");
+
+        let messages: Vec<String> = vfs
+            .log(&merge)
+            .map(|c| c.expect("commit should parse").message)
+            .collect();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], "merge");
+        assert!(messages.contains(&"root".to_string()));
+        // "root" must appear exactly once even though both parents lead to it.
+        assert_eq!(messages.iter().filter(|m| *m == "root").count(), 1);
+    }
+
+    #[test]
+    fn test_log_resolves_short_ref_names() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let root = vfs
+            .create_commit(&tree, &[], sig("alice"), sig("alice"), "root")
+            .expect("create root commit");
+        vfs.create_ref("refs/heads/main", &root)
+            .expect("create ref");
+
+        let messages: Vec<String> = vfs
+            .log("main")
+            .map(|c| c.expect("commit should parse").message)
+            .collect();
+        assert_eq!(messages, vec!["root".to_string()]);
+    }
+
+    #[test]
+    fn test_log_accepts_raw_oid_when_not_a_ref() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let root = vfs
+            .create_commit(&tree, &[], sig("alice"), sig("alice"), "root")
+            .expect("create root commit");
+
+        let messages: Vec<String> = vfs
+            .log(&root)
+            .map(|c| c.expect("commit should parse").message)
+            .collect();
+        assert_eq!(messages, vec!["root".to_string()]);
+    }
+}
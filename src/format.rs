@@ -0,0 +1,52 @@
+/// The hashing scheme a [`crate::GitVfs`] uses for object ids, mirroring
+/// git's own (ongoing) transition from SHA-1 to SHA-256 object formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectFormat {
+    /// 40 hex chars / 20 raw bytes. What upstream git still uses by default.
+    Sha1,
+    /// 64 hex chars / 32 raw bytes.
+    Sha256,
+}
+
+impl ObjectFormat {
+    /// Width of an oid as a hex string.
+    pub(crate) fn hex_width(self) -> usize {
+        match self {
+            ObjectFormat::Sha1 => 40,
+            ObjectFormat::Sha256 => 64,
+        }
+    }
+
+    /// Width of an oid as raw binary, e.g. embedded in a tree entry.
+    pub(crate) fn byte_width(self) -> usize {
+        self.hex_width() / 2
+    }
+
+    pub(crate) fn capability_name(self) -> &'static str {
+        match self {
+            ObjectFormat::Sha1 => "sha1",
+            ObjectFormat::Sha256 => "sha256",
+        }
+    }
+
+    pub(crate) fn from_capability_name(s: &str) -> Option<ObjectFormat> {
+        match s {
+            "sha1" => Some(ObjectFormat::Sha1),
+            "sha256" => Some(ObjectFormat::Sha256),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths() {
+        assert_eq!(ObjectFormat::Sha1.hex_width(), 40);
+        assert_eq!(ObjectFormat::Sha1.byte_width(), 20);
+        assert_eq!(ObjectFormat::Sha256.hex_width(), 64);
+        assert_eq!(ObjectFormat::Sha256.byte_width(), 32);
+    }
+}
@@ -1,19 +1,71 @@
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
+mod bundle;
+mod commit;
+mod format;
+mod refs;
+mod storage;
+mod tree;
+mod typed;
+pub use commit::{Commit, CommitLogIter, Signature};
+pub use format::ObjectFormat;
+pub use tree::{FileMode, TreeEntry};
+pub use typed::{BlobData, TreeData};
+
+/// The kind of a git object, used to build the loose-object header that
+/// precedes its payload when computing the object's id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Blob,
+    Tree,
+    Commit,
+    Tag,
+}
+
+impl ObjectKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ObjectKind::Blob => "blob",
+            ObjectKind::Tree => "tree",
+            ObjectKind::Commit => "commit",
+            ObjectKind::Tag => "tag",
+        }
+    }
+
+    fn from_str(s: &str) -> GitVfsResult<ObjectKind> {
+        match s {
+            "blob" => Ok(ObjectKind::Blob),
+            "tree" => Ok(ObjectKind::Tree),
+            "commit" => Ok(ObjectKind::Commit),
+            "tag" => Ok(ObjectKind::Tag),
+            _ => Err(GitVfsError::InvalidOperation),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum GitVfsError {
     NotFound,
     AlreadyExists,
     InvalidOperation,
+    /// A typed blob ([`TreeData`]) exceeded its declared `MAX_BYTES`.
+    PayloadTooLarge { max: usize, found: usize },
 }
 
 pub type GitVfsResult<T> = Result<T, GitVfsError>;
 
 pub struct GitVfs {
-    objects: HashMap<String, Vec<u8>>, // Stores git objects (blobs, trees, commits)
-    refs: HashMap<String, String>,     // Stores references (branches, tags)
-    head: Option<String>,              // Stores the current HEAD reference
+    // The kind is `None` only for objects stored through the raw
+    // `create_object` escape hatch, which doesn't know (or require) one;
+    // every object created through `store_object` carries it, so most
+    // reads can use the cached kind instead of re-deriving it by brute
+    // force (see `infer_kind`).
+    objects: HashMap<String, (Option<ObjectKind>, Vec<u8>)>,
+    refs: HashMap<String, String>, // Stores references (branches, tags)
+    head: Option<String>,          // Stores the current HEAD reference
+    format: ObjectFormat,          // Hashing scheme used for object ids
 }
 
 impl Default for GitVfs {
@@ -23,29 +75,80 @@ impl Default for GitVfs {
 }
 
 impl GitVfs {
+    /// Creates a VFS hashing objects with [`ObjectFormat::Sha256`], this
+    /// crate's historical default. Use [`GitVfs::with_format`] to interop
+    /// with SHA-1 object stores instead.
     pub fn new() -> Self {
+        Self::with_format(ObjectFormat::Sha256)
+    }
+
+    pub fn with_format(format: ObjectFormat) -> Self {
         GitVfs {
             objects: HashMap::new(),
             refs: HashMap::new(),
             head: None,
+            format,
+        }
+    }
+
+    pub fn object_format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    /// Rejects `oid` unless its hex width matches the configured
+    /// [`ObjectFormat`] and it is made up of valid hex digits.
+    fn validate_oid(&self, oid: &str) -> GitVfsResult<()> {
+        if oid.len() != self.format.hex_width() || !oid.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(GitVfsError::InvalidOperation);
         }
+        Ok(())
     }
 
     pub fn create_object(&mut self, hash: &str, data: &[u8]) -> GitVfsResult<()> {
+        self.validate_oid(hash)?;
         if self.objects.contains_key(hash) {
             return Err(GitVfsError::AlreadyExists);
         }
-        self.objects.insert(hash.to_string(), data.to_vec());
+        self.objects.insert(hash.to_string(), (None, data.to_vec()));
         Ok(())
     }
 
     pub fn get_object(&self, hash: &str) -> GitVfsResult<Vec<u8>> {
+        self.validate_oid(hash)?;
         match self.objects.get(hash) {
-            Some(data) => Ok(data.clone()),
+            Some((_, data)) => Ok(data.clone()),
             None => Err(GitVfsError::NotFound),
         }
     }
 
+    /// Returns the object's kind, using the cached kind recorded at creation
+    /// time when available and otherwise deriving it by brute-force trial of
+    /// [`GitVfs::hash_object`] over each [`ObjectKind`] (see `infer_kind`).
+    fn kind_of(&self, hash: &str, data: &[u8]) -> GitVfsResult<ObjectKind> {
+        match self.objects.get(hash) {
+            Some((Some(kind), _)) => Ok(*kind),
+            _ => self.infer_kind(hash, data),
+        }
+    }
+
+    /// Recovers an object's kind by trying each [`ObjectKind`] variant's
+    /// [`GitVfs::hash_object`] against `data` until one reproduces `hash`.
+    /// Used as a fallback when an object's kind wasn't cached at creation
+    /// time, e.g. objects stored via the untyped [`GitVfs::create_object`].
+    fn infer_kind(&self, hash: &str, data: &[u8]) -> GitVfsResult<ObjectKind> {
+        for kind in [
+            ObjectKind::Blob,
+            ObjectKind::Tree,
+            ObjectKind::Commit,
+            ObjectKind::Tag,
+        ] {
+            if self.hash_object(kind, data) == hash {
+                return Ok(kind);
+            }
+        }
+        Err(GitVfsError::InvalidOperation)
+    }
+
     pub fn create_ref(&mut self, ref_name: &str, hash: &str) -> GitVfsResult<()> {
         self.refs.insert(ref_name.to_string(), hash.to_string());
         Ok(())
@@ -66,11 +169,15 @@ impl GitVfs {
         Ok(())
     }
 
+    /// Points HEAD at `ref_name` symbolically (stored as `"ref: <ref_name>"`),
+    /// so that later updates to that ref are followed automatically. See
+    /// [`GitVfs::set_head_detached`] for pinning HEAD to a raw oid instead,
+    /// and [`GitVfs::resolve_ref`] for following it back to a commit.
     pub fn set_head(&mut self, ref_name: &str) -> GitVfsResult<()> {
         if !self.refs.contains_key(ref_name) {
             return Err(GitVfsError::NotFound);
         }
-        self.head = Some(ref_name.to_string());
+        self.head = Some(format!("ref: {ref_name}"));
         Ok(())
     }
 
@@ -81,19 +188,56 @@ impl GitVfs {
         }
     }
 
-    pub fn create_blob(&mut self, data: &[u8]) -> GitVfsResult<String> {
-        let hash = format!("{}", data.len());
-        self.create_object(&hash, data)?;
+    /// Computes the id a loose git object of `kind` containing `data` would
+    /// have: the digest (under the configured [`ObjectFormat`]) of
+    /// `"<kind> <len>\0"` followed by `data`, hex-encoded. This matches what
+    /// `git hash-object` produces for the corresponding object format.
+    pub fn hash_object(&self, kind: ObjectKind, data: &[u8]) -> String {
+        let header = format!("{} {}\0", kind.as_str(), data.len());
+        match self.format {
+            ObjectFormat::Sha1 => {
+                let mut hasher = Sha1::new();
+                hasher.update(header.as_bytes());
+                hasher.update(data);
+                hex::encode(hasher.finalize())
+            }
+            ObjectFormat::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(header.as_bytes());
+                hasher.update(data);
+                hex::encode(hasher.finalize())
+            }
+        }
+    }
+
+    fn store_object(&mut self, kind: ObjectKind, data: &[u8]) -> GitVfsResult<String> {
+        let hash = self.hash_object(kind, data);
+        if !self.objects.contains_key(&hash) {
+            self.objects.insert(hash.clone(), (Some(kind), data.to_vec()));
+        }
         Ok(hash)
     }
 
+    /// The raw byte width of an object id under the currently active hash.
+    /// Used to size the binary hashes embedded in tree entries.
+    fn hash_byte_len(&self) -> usize {
+        self.format.byte_width()
+    }
+
+    pub fn create_blob(&mut self, data: &[u8]) -> GitVfsResult<String> {
+        self.store_object(ObjectKind::Blob, data)
+    }
+
+    pub fn create_tag(&mut self, data: &[u8]) -> GitVfsResult<String> {
+        self.store_object(ObjectKind::Tag, data)
+    }
+
     pub fn data_sha256(&mut self, data_to_hash: &[u8]) -> String {
         let mut hasher = Sha256::new();
         hasher.update(data_to_hash);
         let result = hasher.finalize();
-        let hex_hash = hex::encode(result);
 
-        hex_hash
+        hex::encode(result)
     }
 }
 
@@ -133,4 +277,65 @@ mod tests {
         let single_hash = hex::encode(Sha256::digest(b"part one part two"));
         assert_eq!(combined_hash, single_hash);
     }
+
+    #[test]
+    fn test_hash_object_includes_loose_header() {
+        let vfs = GitVfs::new();
+        let data = b"hello";
+        let expected = hex::encode(Sha256::digest(b"blob 5\0hello"));
+        assert_eq!(vfs.hash_object(ObjectKind::Blob, data), expected);
+    }
+
+    #[test]
+    fn test_create_blob_is_content_addressed() {
+        let mut vfs = GitVfs::new();
+        let hash = vfs.create_blob(b"hello").expect("create blob");
+        assert_eq!(hash, hex::encode(Sha256::digest(b"blob 5\0hello")));
+        let stored = vfs.get_object(&hash).expect("get blob");
+        assert_eq!(stored, b"hello");
+    }
+
+    #[test]
+    fn test_create_blob_is_idempotent_for_same_content() {
+        let mut vfs = GitVfs::new();
+        let first = vfs.create_blob(b"same data").expect("create blob");
+        let second = vfs.create_blob(b"same data").expect("create blob again");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_different_kinds_hash_differently() {
+        let vfs = GitVfs::new();
+        let blob_hash = vfs.hash_object(ObjectKind::Blob, b"tree 0\0");
+        let tree_hash = vfs.hash_object(ObjectKind::Tree, b"");
+        assert_ne!(blob_hash, tree_hash);
+    }
+
+    #[test]
+    fn test_sha1_format_produces_40_char_hex_ids() {
+        let mut vfs = GitVfs::with_format(ObjectFormat::Sha1);
+        let hash = vfs.create_blob(b"hello").expect("create blob");
+        assert_eq!(hash.len(), 40);
+    }
+
+    #[test]
+    fn test_sha256_format_produces_64_char_hex_ids() {
+        let mut vfs = GitVfs::with_format(ObjectFormat::Sha256);
+        let hash = vfs.create_blob(b"hello").expect("create blob");
+        assert_eq!(hash.len(), 64);
+    }
+
+    #[test]
+    fn test_get_object_rejects_oid_of_wrong_width_for_format() {
+        let mut vfs = GitVfs::with_format(ObjectFormat::Sha1);
+        let short_oid = "a".repeat(40);
+        vfs.create_object(&short_oid, b"data").expect("create object");
+
+        let sha256_shaped = "a".repeat(64);
+        assert_eq!(
+            vfs.get_object(&sha256_shaped),
+            Err(GitVfsError::InvalidOperation)
+        );
+        assert!(vfs.get_object(&short_oid).is_ok());
+    }
 }
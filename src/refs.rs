@@ -0,0 +1,178 @@
+use crate::{GitVfs, GitVfsError, GitVfsResult};
+
+/// Symbolic refs (and HEAD) are stored as `"ref: <target>"`; following more
+/// hops than this is treated as a cycle.
+const MAX_REF_HOPS: usize = 10;
+
+impl GitVfs {
+    /// Resolves `name` to a concrete object id: follows symbolic ref chains
+    /// (`"ref: refs/heads/main"`, including a symbolic `HEAD`) down to a
+    /// stored oid, then peels any annotated tag objects found there to the
+    /// commit they ultimately point at.
+    ///
+    /// `name` may be `"HEAD"`, a fully-qualified ref, a short name (probed
+    /// as `refs/heads/<name>`, then `refs/tags/<name>`, then the literal
+    /// name), or a raw oid.
+    pub fn resolve_ref(&self, name: &str) -> GitVfsResult<String> {
+        let target = self.resolve_symbolic(name)?;
+        self.peel_to_commit(&target)
+    }
+
+    /// Follows symbolic ref chains down to a concrete stored oid, without
+    /// peeling annotated tags. Exposed crate-internally for bundle export,
+    /// which needs to advertise the un-peeled oid a ref currently points at.
+    pub(crate) fn resolve_symbolic(&self, name: &str) -> GitVfsResult<String> {
+        let mut current = self.initial_ref_value(name)?;
+        let mut hops = 0;
+        while let Some(target) = current.strip_prefix("ref: ") {
+            hops += 1;
+            if hops > MAX_REF_HOPS {
+                return Err(GitVfsError::InvalidOperation);
+            }
+            current = self.initial_ref_value(target)?;
+        }
+        Ok(current)
+    }
+
+    /// Looks up the raw stored value (which may itself be symbolic) for
+    /// `name`, without following or peeling it.
+    fn initial_ref_value(&self, name: &str) -> GitVfsResult<String> {
+        if name == "HEAD" {
+            return self.head.clone().ok_or(GitVfsError::NotFound);
+        }
+        for candidate in [format!("refs/heads/{name}"), format!("refs/tags/{name}")] {
+            if let Some(value) = self.refs.get(&candidate) {
+                return Ok(value.clone());
+            }
+        }
+        if let Some(value) = self.refs.get(name) {
+            return Ok(value.clone());
+        }
+        if self.objects.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        Err(GitVfsError::NotFound)
+    }
+
+    /// Follows `object <oid>` links out of annotated tag objects until
+    /// reaching something that isn't shaped like a tag (a commit, in the
+    /// common case).
+    fn peel_to_commit(&self, oid: &str) -> GitVfsResult<String> {
+        let mut current = oid.to_string();
+        loop {
+            // An oid that can't be read back (never stored, or shaped for a
+            // different object format) can't be a tag to peel through;
+            // treat it as the final answer rather than erroring.
+            let data = match self.get_object(&current) {
+                Ok(data) => data,
+                Err(_) => return Ok(current),
+            };
+            let Some(rest) = data.strip_prefix(b"object ") else {
+                return Ok(current);
+            };
+            let newline = rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .ok_or(GitVfsError::InvalidOperation)?;
+            current = std::str::from_utf8(&rest[..newline])
+                .map_err(|_| GitVfsError::InvalidOperation)?
+                .to_string();
+        }
+    }
+
+    /// Points HEAD directly at `oid` (detached HEAD), bypassing symbolic
+    /// resolution.
+    pub fn set_head_detached(&mut self, oid: &str) -> GitVfsResult<()> {
+        self.head = Some(oid.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_ref_follows_symbolic_head() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let commit = vfs
+            .create_commit(
+                &tree,
+                &[],
+                crate::Signature {
+                    name: "alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    timestamp: 1,
+                    tz: "+0000".to_string(),
+                },
+                crate::Signature {
+                    name: "alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    timestamp: 1,
+                    tz: "+0000".to_string(),
+                },
+                "root",
+            )
+            .expect("create commit");
+        vfs.create_ref("refs/heads/main", &commit)
+            .expect("create ref");
+        vfs.set_head("refs/heads/main").expect("set head");
+
+        assert_eq!(vfs.resolve_ref("HEAD").unwrap(), commit);
+    }
+
+    #[test]
+    fn test_resolve_ref_probes_short_names_in_priority_order() {
+        let mut vfs = GitVfs::new();
+        vfs.create_ref("refs/tags/v1", "tag-oid").expect("create ref");
+        assert_eq!(vfs.resolve_ref("v1").unwrap(), "tag-oid");
+
+        vfs.create_ref("refs/heads/v1", "branch-oid")
+            .expect("create ref");
+        // refs/heads/<name> takes priority over refs/tags/<name>.
+        assert_eq!(vfs.resolve_ref("v1").unwrap(), "branch-oid");
+    }
+
+    #[test]
+    fn test_resolve_ref_detects_cycle() {
+        let mut vfs = GitVfs::new();
+        vfs.create_ref("refs/heads/a", "ref: refs/heads/b")
+            .expect("create ref");
+        vfs.create_ref("refs/heads/b", "ref: refs/heads/a")
+            .expect("create ref");
+        assert_eq!(
+            vfs.resolve_ref("a"),
+            Err(GitVfsError::InvalidOperation)
+        );
+    }
+
+    #[test]
+    fn test_resolve_ref_peels_annotated_tag_to_commit() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let sig = crate::Signature {
+            name: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            timestamp: 1,
+            tz: "+0000".to_string(),
+        };
+        let commit = vfs
+            .create_commit(&tree, &[], sig.clone(), sig, "root")
+            .expect("create commit");
+        let tag_body = format!("object {commit}\ntype commit\ntag v1.0\n\nrelease");
+        let tag_oid = vfs.create_tag(tag_body.as_bytes()).expect("create tag");
+        vfs.create_ref("refs/tags/v1.0", &tag_oid)
+            .expect("create ref");
+
+        assert_eq!(vfs.resolve_ref("v1.0").unwrap(), commit);
+    }
+
+    #[test]
+    fn test_set_head_detached_resolves_directly() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        vfs.set_head_detached(&tree).expect("set detached head");
+        assert_eq!(vfs.resolve_ref("HEAD").unwrap(), tree);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{GitVfs, GitVfsError, GitVfsResult, ObjectFormat, ObjectKind};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+impl GitVfs {
+    /// Writes every in-memory object, ref, and HEAD to `path` in git's
+    /// on-disk loose-object layout: each object zlib-deflated as
+    /// `"<kind> <len>\0<payload>"` under `objects/<first-2-hex>/<rest-hex>`,
+    /// refs under their full `refs/...` path, and HEAD as a plain file.
+    pub fn flush_to(&self, path: impl AsRef<Path>) -> GitVfsResult<()> {
+        let path = path.as_ref();
+        let io_err = |_: std::io::Error| GitVfsError::InvalidOperation;
+
+        let objects_dir = path.join("objects");
+        for (hash, (kind, data)) in &self.objects {
+            let kind = match kind {
+                Some(kind) => *kind,
+                None => self.infer_kind(hash, data)?,
+            };
+            let mut loose = format!("{} {}\0", kind.as_str(), data.len()).into_bytes();
+            loose.extend_from_slice(data);
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&loose).map_err(io_err)?;
+            let compressed = encoder.finish().map_err(io_err)?;
+
+            let (dir_name, file_name) = hash.split_at(2);
+            let dir = objects_dir.join(dir_name);
+            fs::create_dir_all(&dir).map_err(io_err)?;
+            fs::write(dir.join(file_name), compressed).map_err(io_err)?;
+        }
+
+        for (ref_name, target) in &self.refs {
+            let ref_path = path.join(ref_name);
+            if let Some(parent) = ref_path.parent() {
+                fs::create_dir_all(parent).map_err(io_err)?;
+            }
+            fs::write(ref_path, format!("{target}\n")).map_err(io_err)?;
+        }
+
+        if let Some(head) = &self.head {
+            fs::write(path.join("HEAD"), format!("{head}\n")).map_err(io_err)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads a `GitVfs` back from a directory written by
+    /// [`GitVfs::flush_to`], verifying that each loose object's recomputed
+    /// id matches the path it was stored at.
+    ///
+    /// The [`ObjectFormat`](crate::ObjectFormat) the store was persisted
+    /// with is detected from the hex width of its loose-object paths (38
+    /// hex chars after the 2-char directory prefix for SHA-1, 62 for
+    /// SHA-256), falling back to [`ObjectFormat::Sha256`] for an empty or
+    /// missing `objects` directory.
+    pub fn open(path: impl AsRef<Path>) -> GitVfsResult<GitVfs> {
+        let path = path.as_ref();
+        let io_err = |_: std::io::Error| GitVfsError::InvalidOperation;
+
+        let objects_dir = path.join("objects");
+        let mut loose_objects = Vec::new();
+        if objects_dir.is_dir() {
+            for dir_entry in fs::read_dir(&objects_dir).map_err(io_err)? {
+                let dir_entry = dir_entry.map_err(io_err)?;
+                if !dir_entry.path().is_dir() {
+                    continue;
+                }
+                let prefix = dir_entry
+                    .file_name()
+                    .into_string()
+                    .map_err(|_| GitVfsError::InvalidOperation)?;
+                for file_entry in fs::read_dir(dir_entry.path()).map_err(io_err)? {
+                    let file_entry = file_entry.map_err(io_err)?;
+                    let suffix = file_entry
+                        .file_name()
+                        .into_string()
+                        .map_err(|_| GitVfsError::InvalidOperation)?;
+                    let oid = format!("{prefix}{suffix}");
+                    let compressed = fs::read(file_entry.path()).map_err(io_err)?;
+                    loose_objects.push((oid, compressed));
+                }
+            }
+        }
+
+        let format = match loose_objects.first() {
+            Some((oid, _)) if oid.len() == ObjectFormat::Sha1.hex_width() => ObjectFormat::Sha1,
+            Some((oid, _)) if oid.len() == ObjectFormat::Sha256.hex_width() => ObjectFormat::Sha256,
+            Some(_) => return Err(GitVfsError::InvalidOperation),
+            None => ObjectFormat::Sha256,
+        };
+        let mut vfs = GitVfs::with_format(format);
+
+        for (oid, compressed) in loose_objects {
+            let mut raw = Vec::new();
+            ZlibDecoder::new(&compressed[..])
+                .read_to_end(&mut raw)
+                .map_err(io_err)?;
+
+            let nul = raw
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or(GitVfsError::InvalidOperation)?;
+            let header =
+                std::str::from_utf8(&raw[..nul]).map_err(|_| GitVfsError::InvalidOperation)?;
+            let (kind_str, len_str) = header
+                .split_once(' ')
+                .ok_or(GitVfsError::InvalidOperation)?;
+            let kind = ObjectKind::from_str(kind_str)?;
+            let declared_len: usize =
+                len_str.parse().map_err(|_| GitVfsError::InvalidOperation)?;
+            let payload = raw[nul + 1..].to_vec();
+            if payload.len() != declared_len {
+                return Err(GitVfsError::InvalidOperation);
+            }
+
+            if vfs.hash_object(kind, &payload) != oid {
+                return Err(GitVfsError::InvalidOperation);
+            }
+            vfs.objects.insert(oid, (Some(kind), payload));
+        }
+
+        let refs_dir = path.join("refs");
+        if refs_dir.is_dir() {
+            collect_refs(path, &refs_dir, &mut vfs.refs)?;
+        }
+
+        let head_path = path.join("HEAD");
+        if head_path.is_file() {
+            let content = fs::read_to_string(&head_path).map_err(io_err)?;
+            vfs.head = Some(content.trim_end().to_string());
+        }
+
+        Ok(vfs)
+    }
+}
+
+fn collect_refs(root: &Path, dir: &Path, refs: &mut HashMap<String, String>) -> GitVfsResult<()> {
+    let io_err = |_: std::io::Error| GitVfsError::InvalidOperation;
+    for entry in fs::read_dir(dir).map_err(io_err)? {
+        let entry = entry.map_err(io_err)?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_refs(root, &path, refs)?;
+        } else {
+            let rel = path
+                .strip_prefix(root)
+                .map_err(|_| GitVfsError::InvalidOperation)?;
+            let name = rel.to_string_lossy().replace(std::path::MAIN_SEPARATOR, "/");
+            let content = fs::read_to_string(&path).map_err(io_err)?;
+            refs.insert(name, content.trim_end().to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Signature;
+
+    fn sig() -> Signature {
+        Signature {
+            name: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            timestamp: 1,
+            tz: "+0000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_flush_then_open_round_trips() {
+        let mut vfs = GitVfs::new();
+        let blob = vfs.create_blob(b"hello").expect("create blob");
+        let tree = vfs
+            .create_tree(&[crate::TreeEntry {
+                mode: crate::FileMode::Regular,
+                name: "file.txt".to_string(),
+                hash: blob,
+            }])
+            .expect("create tree");
+        let commit = vfs
+            .create_commit(&tree, &[], sig(), sig(), "root")
+            .expect("create commit");
+        vfs.create_ref("refs/heads/main", &commit)
+            .expect("create ref");
+        vfs.set_head("refs/heads/main").expect("set head");
+
+        let dir = std::env::temp_dir().join(format!("git-vfs-test-{commit}"));
+        vfs.flush_to(&dir).expect("flush to disk");
+
+        let reopened = GitVfs::open(&dir).expect("open from disk");
+        assert_eq!(reopened.get_ref("refs/heads/main").unwrap(), commit);
+        assert_eq!(reopened.get_head().unwrap(), "ref: refs/heads/main");
+        let reopened_commit = reopened.read_commit(&commit).expect("read commit");
+        assert_eq!(reopened_commit.message, "root");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_flush_then_open_round_trips_sha1_format() {
+        let mut vfs = GitVfs::with_format(ObjectFormat::Sha1);
+        let blob = vfs.create_blob(b"hello").expect("create blob");
+
+        let dir = std::env::temp_dir().join(format!("git-vfs-test-sha1-{blob}"));
+        vfs.flush_to(&dir).expect("flush to disk");
+
+        let reopened = GitVfs::open(&dir).expect("open from disk");
+        assert_eq!(reopened.object_format(), ObjectFormat::Sha1);
+        assert_eq!(reopened.get_object(&blob).expect("read blob"), b"hello");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_open_rejects_tampered_object() {
+        let mut vfs = GitVfs::new();
+        let blob = vfs.create_blob(b"hello").expect("create blob");
+
+        let dir = std::env::temp_dir().join(format!("git-vfs-test-tamper-{blob}"));
+        vfs.flush_to(&dir).expect("flush to disk");
+
+        let (dir_name, file_name) = blob.split_at(2);
+        let object_path = dir.join("objects").join(dir_name).join(file_name);
+        let loose = b"blob 3\0bad".to_vec();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&loose).expect("compress");
+        fs::write(&object_path, encoder.finish().expect("finish")).expect("write tampered object");
+
+        assert!(GitVfs::open(&dir).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{GitVfs, GitVfsError, GitVfsResult, ObjectKind};
+
+/// The mode bits git stores alongside a tree entry, identifying what kind of
+/// object the entry's hash points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileMode {
+    /// A regular, non-executable file (`100644`).
+    Regular,
+    /// An executable file (`100755`).
+    Executable,
+    /// A subtree / directory (`40000`).
+    Directory,
+    /// A symbolic link, whose blob content is the link target (`120000`).
+    Symlink,
+}
+
+impl FileMode {
+    fn as_octal(self) -> &'static str {
+        match self {
+            FileMode::Regular => "100644",
+            FileMode::Executable => "100755",
+            FileMode::Directory => "40000",
+            FileMode::Symlink => "120000",
+        }
+    }
+
+    fn from_octal(s: &str) -> GitVfsResult<FileMode> {
+        match s {
+            "100644" => Ok(FileMode::Regular),
+            "100755" => Ok(FileMode::Executable),
+            "40000" => Ok(FileMode::Directory),
+            "120000" => Ok(FileMode::Symlink),
+            _ => Err(GitVfsError::InvalidOperation),
+        }
+    }
+
+    fn is_directory(self) -> bool {
+        matches!(self, FileMode::Directory)
+    }
+}
+
+/// A single entry in a git tree object: a name, the mode of what it points
+/// at, and the hash of that object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub mode: FileMode,
+    pub name: String,
+    pub hash: String,
+}
+
+/// Git's tree-entry sort key: directory names sort as if they had a
+/// trailing `/`, so e.g. `"foo.txt"` sorts before the directory `"foo"`.
+fn sort_key(entry: &TreeEntry) -> String {
+    if entry.mode.is_directory() {
+        format!("{}/", entry.name)
+    } else {
+        entry.name.clone()
+    }
+}
+
+impl GitVfs {
+    /// Encodes `entries` in canonical tree order (`"<mode> <name>\0<raw hash>"`,
+    /// sorted by [`sort_key`]) and stores the result as a tree object.
+    pub fn create_tree(&mut self, entries: &[TreeEntry]) -> GitVfsResult<String> {
+        let mut sorted = entries.to_vec();
+        sorted.sort_by_key(sort_key);
+
+        let mut buf = Vec::new();
+        for entry in &sorted {
+            buf.extend_from_slice(entry.mode.as_octal().as_bytes());
+            buf.push(b' ');
+            buf.extend_from_slice(entry.name.as_bytes());
+            buf.push(0);
+            self.validate_oid(&entry.hash)?;
+            let raw = hex::decode(&entry.hash).map_err(|_| GitVfsError::InvalidOperation)?;
+            buf.extend_from_slice(&raw);
+        }
+        self.store_object(ObjectKind::Tree, &buf)
+    }
+
+    /// Parses a tree object back into its entries.
+    pub fn read_tree(&self, hash: &str) -> GitVfsResult<Vec<TreeEntry>> {
+        let data = self.get_object(hash)?;
+        let hash_len = self.hash_byte_len();
+        let mut entries = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            let space = i + data[i..]
+                .iter()
+                .position(|&b| b == b' ')
+                .ok_or(GitVfsError::InvalidOperation)?;
+            let mode_str =
+                std::str::from_utf8(&data[i..space]).map_err(|_| GitVfsError::InvalidOperation)?;
+            let mode = FileMode::from_octal(mode_str)?;
+
+            let nul = space
+                + 1
+                + data[space + 1..]
+                    .iter()
+                    .position(|&b| b == 0)
+                    .ok_or(GitVfsError::InvalidOperation)?;
+            let name = std::str::from_utf8(&data[space + 1..nul])
+                .map_err(|_| GitVfsError::InvalidOperation)?
+                .to_string();
+
+            let hash_start = nul + 1;
+            let hash_end = hash_start + hash_len;
+            if hash_end > data.len() {
+                return Err(GitVfsError::InvalidOperation);
+            }
+            let hash = hex::encode(&data[hash_start..hash_end]);
+            entries.push(TreeEntry { mode, name, hash });
+            i = hash_end;
+        }
+        Ok(entries)
+    }
+
+    /// Writes `blob_hash` at `path` (e.g. `"a/b/file"`) inside `tree`,
+    /// creating any intermediate subtrees, and returns the hash of the new
+    /// root tree.
+    pub fn write_path(&mut self, tree: &str, path: &str, blob_hash: &str) -> GitVfsResult<String> {
+        let components: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if components.is_empty() {
+            return Err(GitVfsError::InvalidOperation);
+        }
+        let entries = self.read_tree(tree)?;
+        let new_entries = self.write_path_rec(entries, &components, blob_hash)?;
+        self.create_tree(&new_entries)
+    }
+
+    fn write_path_rec(
+        &mut self,
+        mut entries: Vec<TreeEntry>,
+        components: &[&str],
+        blob_hash: &str,
+    ) -> GitVfsResult<Vec<TreeEntry>> {
+        let (head, rest) = components
+            .split_first()
+            .ok_or(GitVfsError::InvalidOperation)?;
+
+        if rest.is_empty() {
+            if entries.iter().any(|e| e.name == *head && e.mode.is_directory()) {
+                return Err(GitVfsError::InvalidOperation);
+            }
+            entries.retain(|e| e.name != *head);
+            entries.push(TreeEntry {
+                mode: FileMode::Regular,
+                name: (*head).to_string(),
+                hash: blob_hash.to_string(),
+            });
+        } else {
+            let existing = entries.iter().position(|e| e.name == *head);
+            if let Some(idx) = existing {
+                if !entries[idx].mode.is_directory() {
+                    return Err(GitVfsError::InvalidOperation);
+                }
+            }
+            let child_entries = match existing {
+                Some(idx) => self.read_tree(&entries[idx].hash)?,
+                None => Vec::new(),
+            };
+            let new_child_entries = self.write_path_rec(child_entries, rest, blob_hash)?;
+            let new_child_hash = self.create_tree(&new_child_entries)?;
+            match existing {
+                Some(idx) => entries[idx].hash = new_child_hash,
+                None => entries.push(TreeEntry {
+                    mode: FileMode::Directory,
+                    name: (*head).to_string(),
+                    hash: new_child_hash,
+                }),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Walks `path` (e.g. `"a/b/file"`) inside `tree` and returns the hash
+    /// of whatever is at the end of it.
+    pub fn read_path(&self, tree: &str, path: &str) -> GitVfsResult<String> {
+        let components: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if components.is_empty() {
+            return Err(GitVfsError::InvalidOperation);
+        }
+
+        let mut current = tree.to_string();
+        for (i, component) in components.iter().enumerate() {
+            let entries = self.read_tree(&current)?;
+            let entry = entries
+                .into_iter()
+                .find(|e| &e.name == component)
+                .ok_or(GitVfsError::NotFound)?;
+            if i == components.len() - 1 {
+                return Ok(entry.hash);
+            }
+            current = entry.hash;
+        }
+        unreachable!("empty components already rejected above")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_and_read_tree_round_trips() {
+        let mut vfs = GitVfs::new();
+        let blob_hash = vfs.create_blob(b"hello").expect("create blob");
+        let entries = vec![TreeEntry {
+            mode: FileMode::Regular,
+            name: "file.txt".to_string(),
+            hash: blob_hash.clone(),
+        }];
+        let tree_hash = vfs.create_tree(&entries).expect("create tree");
+        let read_back = vfs.read_tree(&tree_hash).expect("read tree");
+        assert_eq!(read_back, entries);
+    }
+
+    #[test]
+    fn test_tree_entries_are_sorted_with_directories_as_trailing_slash() {
+        let mut vfs = GitVfs::new();
+        let blob_hash = vfs.create_blob(b"x").expect("create blob");
+        let entries = vec![
+            TreeEntry {
+                mode: FileMode::Directory,
+                name: "foo".to_string(),
+                hash: blob_hash.clone(),
+            },
+            TreeEntry {
+                mode: FileMode::Regular,
+                name: "foo.txt".to_string(),
+                hash: blob_hash.clone(),
+            },
+        ];
+        let tree_hash = vfs.create_tree(&entries).expect("create tree");
+        let read_back = vfs.read_tree(&tree_hash).expect("read tree");
+        // "foo.txt" sorts before the directory "foo" (compared as "foo/").
+        assert_eq!(read_back[0].name, "foo.txt");
+        assert_eq!(read_back[1].name, "foo");
+    }
+
+    #[test]
+    fn test_write_path_and_read_path_nested() {
+        let mut vfs = GitVfs::new();
+        let root = vfs.create_tree(&[]).expect("create empty tree");
+        let blob_hash = vfs.create_blob(b"contents").expect("create blob");
+
+        let new_root = vfs
+            .write_path(&root, "a/b/file", &blob_hash)
+            .expect("write path");
+        let found = vfs.read_path(&new_root, "a/b/file").expect("read path");
+        assert_eq!(found, blob_hash);
+    }
+
+    #[test]
+    fn test_write_path_preserves_siblings() {
+        let mut vfs = GitVfs::new();
+        let root = vfs.create_tree(&[]).expect("create empty tree");
+        let blob_one = vfs.create_blob(b"one").expect("create blob");
+        let blob_two = vfs.create_blob(b"two").expect("create blob");
+
+        let root = vfs
+            .write_path(&root, "dir/one.txt", &blob_one)
+            .expect("write one");
+        let root = vfs
+            .write_path(&root, "dir/two.txt", &blob_two)
+            .expect("write two");
+
+        assert_eq!(vfs.read_path(&root, "dir/one.txt").unwrap(), blob_one);
+        assert_eq!(vfs.read_path(&root, "dir/two.txt").unwrap(), blob_two);
+    }
+
+    #[test]
+    fn test_read_path_missing_entry_not_found() {
+        let mut vfs = GitVfs::new();
+        let root = vfs.create_tree(&[]).expect("create empty tree");
+        assert_eq!(
+            vfs.read_path(&root, "missing"),
+            Err(GitVfsError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_write_path_rejects_descending_through_a_file() {
+        let mut vfs = GitVfs::new();
+        let root = vfs.create_tree(&[]).expect("create empty tree");
+        let blob_one = vfs.create_blob(b"one").expect("create blob");
+        let blob_two = vfs.create_blob(b"two").expect("create blob");
+
+        let root = vfs
+            .write_path(&root, "a", &blob_one)
+            .expect("write file at a");
+        assert_eq!(
+            vfs.write_path(&root, "a/b", &blob_two),
+            Err(GitVfsError::InvalidOperation)
+        );
+        // The original file must survive the rejected write untouched.
+        assert_eq!(vfs.read_path(&root, "a").unwrap(), blob_one);
+    }
+
+    #[test]
+    fn test_write_path_rejects_file_where_directory_exists() {
+        let mut vfs = GitVfs::new();
+        let root = vfs.create_tree(&[]).expect("create empty tree");
+        let blob_one = vfs.create_blob(b"one").expect("create blob");
+        let blob_two = vfs.create_blob(b"two").expect("create blob");
+
+        let root = vfs
+            .write_path(&root, "a/b", &blob_one)
+            .expect("write path under directory a");
+        assert_eq!(
+            vfs.write_path(&root, "a", &blob_two),
+            Err(GitVfsError::InvalidOperation)
+        );
+        // The original subtree must survive the rejected write untouched.
+        assert_eq!(vfs.read_path(&root, "a/b").unwrap(), blob_one);
+    }
+}
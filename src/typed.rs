@@ -0,0 +1,127 @@
+use crate::{GitVfs, GitVfsError, GitVfsResult};
+
+/// A typed payload that can be stored in and read back from a single blob.
+pub trait BlobData: Sized {
+    /// The largest encoded size this type will ever produce or accept.
+    const MAX_BYTES: usize;
+
+    /// Decodes a value from a blob's raw bytes.
+    fn from_blob(data: &[u8]) -> GitVfsResult<Self>;
+
+    /// Encodes this value as the bytes a blob should store.
+    fn write_blob(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()>;
+}
+
+/// A [`BlobData`] that lives at a conventional, fixed path in a tree (e.g. a
+/// repo's config, identity, or index file).
+pub trait TreeData: BlobData {
+    /// The tree-entry name this type is stored under.
+    const BLOB_NAME: &'static str;
+}
+
+impl GitVfs {
+    /// Reads the blob named `T::BLOB_NAME` out of `tree_hash` and decodes it
+    /// as `T`, rejecting anything larger than `T::MAX_BYTES`.
+    pub fn get_typed<T: TreeData>(&self, tree_hash: &str) -> GitVfsResult<T> {
+        let blob_hash = self.read_path(tree_hash, T::BLOB_NAME)?;
+        let data = self.get_object(&blob_hash)?;
+        if data.len() > T::MAX_BYTES {
+            return Err(GitVfsError::PayloadTooLarge {
+                max: T::MAX_BYTES,
+                found: data.len(),
+            });
+        }
+        T::from_blob(&data)
+    }
+
+    /// Encodes `value` as a blob and writes it at `T::BLOB_NAME` inside
+    /// `tree_hash`, returning the hash of the new root tree.
+    pub fn put_typed<T: TreeData>(&mut self, tree_hash: &str, value: &T) -> GitVfsResult<String> {
+        let mut buf = Vec::new();
+        value
+            .write_blob(&mut buf)
+            .map_err(|_| GitVfsError::InvalidOperation)?;
+        if buf.len() > T::MAX_BYTES {
+            return Err(GitVfsError::PayloadTooLarge {
+                max: T::MAX_BYTES,
+                found: buf.len(),
+            });
+        }
+        let blob_hash = self.create_blob(&buf)?;
+        self.write_path(tree_hash, T::BLOB_NAME, &blob_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Identity {
+        name: String,
+    }
+
+    impl BlobData for Identity {
+        const MAX_BYTES: usize = 64;
+
+        fn from_blob(data: &[u8]) -> GitVfsResult<Self> {
+            let name = std::str::from_utf8(data)
+                .map_err(|_| GitVfsError::InvalidOperation)?
+                .to_string();
+            Ok(Identity { name })
+        }
+
+        fn write_blob(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+            writer.write_all(self.name.as_bytes())
+        }
+    }
+
+    impl TreeData for Identity {
+        const BLOB_NAME: &'static str = "identity";
+    }
+
+    #[test]
+    fn test_put_then_get_typed_round_trips() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let identity = Identity {
+            name: "alice".to_string(),
+        };
+
+        let new_tree = vfs
+            .put_typed(&tree, &identity)
+            .expect("put typed value");
+        let read_back: Identity = vfs.get_typed(&new_tree).expect("get typed value");
+        assert_eq!(read_back, identity);
+    }
+
+    #[test]
+    fn test_get_typed_rejects_oversized_payload() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+        let blob = vfs
+            .create_blob(&[b'x'; Identity::MAX_BYTES + 1])
+            .expect("create oversized blob");
+        let tree = vfs
+            .write_path(&tree, Identity::BLOB_NAME, &blob)
+            .expect("write path");
+
+        let result: GitVfsResult<Identity> = vfs.get_typed(&tree);
+        assert_eq!(
+            result,
+            Err(GitVfsError::PayloadTooLarge {
+                max: Identity::MAX_BYTES,
+                found: Identity::MAX_BYTES + 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_typed_missing_entry_not_found() {
+        let mut vfs = GitVfs::new();
+        let tree = vfs.create_tree(&[]).expect("create tree");
+
+        let result: GitVfsResult<Identity> = vfs.get_typed(&tree);
+        assert_eq!(result, Err(GitVfsError::NotFound));
+    }
+}